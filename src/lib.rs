@@ -11,113 +11,121 @@
 //  You should have received a copy of the GNU Lesser General Public License along with this
 //  program.  If not, see <http://www.gnu.org/licenses/>.
 #![feature(slicing_syntax)]
+#![feature(unsafe_destructor)]
 #![allow(unstable)]
 
 extern crate core;
 extern crate alloc;
 
+pub mod text;
+
 use core::fmt;
 
-use std::collections::ring_buf::RingBuf;
+use std::cmp;
 use std::iter::FromIterator;
 use std::cmp::Ordering;
-use std::ops::{Index, IndexMut};
+use std::ops::{Index, IndexMut, Range, RangeFrom, RangeTo, RangeFull};
+use std::ptr;
+use std::slice;
+
+/// Types that can be used as the `range` argument to `GapBuffer::drain`, covering the same set
+/// of slicing-syntax forms the indexing operator accepts (`a..b`, `a..`, `..b`, `..`).
+pub trait RangeArgument {
+    fn start(&self) -> Option<usize>;
+    fn end(&self) -> Option<usize>;
+}
+
+impl RangeArgument for Range<usize> {
+    fn start(&self) -> Option<usize> { Some(self.start) }
+    fn end(&self) -> Option<usize> { Some(self.end) }
+}
+
+impl RangeArgument for RangeFrom<usize> {
+    fn start(&self) -> Option<usize> { Some(self.start) }
+    fn end(&self) -> Option<usize> { None }
+}
+
+impl RangeArgument for RangeTo<usize> {
+    fn start(&self) -> Option<usize> { None }
+    fn end(&self) -> Option<usize> { Some(self.end) }
+}
+
+impl RangeArgument for RangeFull {
+    fn start(&self) -> Option<usize> { None }
+    fn end(&self) -> Option<usize> { None }
+}
 
 /// A GapBuffer is a dynamic array which implements methods to shift the empty portion of the
 /// array around so that modifications can occur at any point in the array. It is optimized for
 /// data structures in which insertions and deletions tend to occur in sequence within the same
 /// area of the array, such as a buffer for a text editor.
-#[derive(Clone,Default)]
+///
+/// Internally, a GapBuffer is a single contiguous allocation (`storage`) holding the elements
+/// before the gap at its front, the elements after the gap at its back, and a run of
+/// uninitialized slots (`gap`) in between.  `storage`'s own length is always kept at zero --
+/// every element lives in its spare capacity and is managed by hand -- so that the prefix and
+/// suffix runs are each a genuine contiguous `&[T]`.
 pub struct GapBuffer<T> {
-    /// The start offset of the ring buffer.  This is necessary in order to prevent leftward
-    /// motion from wrapping around from the conceptual front of the buffer to the back (or vice
-    /// versa).
-    offset: usize,
-    /// The backing ring buffer.  Pushing onto the back is considered to insert a character
-    /// into the leftmost empty slot in the gap, while popping from the front is considered
-    /// deleting the leftmost nonempty slot after the gap.  Moving the gap right means cycling the
-    /// first element to the back; moving left means cycling the last element to the front.
-    buf: RingBuf<T>,
+    storage: Vec<T>,
+    /// The uninitialized run in the middle of `storage`.  `gap.start` is the cursor position.
+    gap: Range<usize>,
 }
 
 impl<T> GapBuffer<T> {
     ///Constructs an empty GapBuffer.
     pub fn new() -> GapBuffer<T> {
         GapBuffer {
-            buf: RingBuf::new(),
-            offset: 0,
+            storage: Vec::new(),
+            gap: 0..0,
         }
     }
 
     ///Constructs a GapBuffer with a given initial capacity.
     pub fn with_capacity(n: usize) -> GapBuffer<T> {
+        let storage: Vec<T> = Vec::with_capacity(n);
+        let cap = storage.capacity();
         GapBuffer {
-            buf: RingBuf::with_capacity(n),
-            offset: 0,
+            storage: storage,
+            gap: 0..cap,
         }
     }
 
-    fn get_idx(&self, i: usize) -> usize {
-        if i < self.offset {
-            // Left of cursor, so indexing starts at self.len() - offset.
-            // Note the order: (self.len() - offset) should be evaluated first, since it is
-            // guaranteed to be nonnegative, and then i should be added (it cannot exceed
-            // self.len() since i < offset, hence it cannot overflow).
-            (self.len() - self.offset) + i
-        } else if i < self.len() {
-            // At or right of cursor, subtract offset.
-            i - self.offset
-        } else {
-            // i out of bounds--leave it that way.
-            i
-        }
+    /// Raw pointer to the storage slot at a given physical index.  Does not check bounds.
+    fn space(&self, index: usize) -> *const T {
+        unsafe { self.storage.as_ptr().offset(index as isize) }
     }
 
+    /// Mutable counterpart to `space`.
+    fn space_mut(&mut self, index: usize) -> *mut T {
+        unsafe { self.storage.as_mut_ptr().offset(index as isize) }
+    }
 
-    /// Shift the gap in the gap buffer.  Note: does not perform bounds checks.
-    fn shift(&mut self, i: usize) {
-        // Since the caller should have checked bounds already, unwrap() in this function should
-        // never fail.
-        match i.cmp(&self.offset) {
-            // Already at the correct position, don't do anything
-            Ordering::Equal => return,
-            // Need to move left
-            Ordering::Less => {
-                // Moving left means cycling the last element to the front.
-                let mut last = self.buf.pop_back().unwrap();
-                self.offset -= 1;
-                while i < self.offset {
-                    self.buf.push_front(last);
-                    last = self.buf.pop_back().unwrap();
-                    self.offset -= 1;
-                }
-                self.buf.push_front(last);
-            },
-            // Need to move right
-            Ordering::Greater => {
-                // Moving right means cycling the first element to the back.
-                let mut first = self.buf.pop_front().unwrap();
-                self.offset += 1;
-                while i > self.offset {
-                    self.buf.push_back(first);
-                    first = self.buf.pop_front().unwrap();
-                    self.offset += 1;
-                }
-                self.buf.push_back(first);
-            }
+    /// Maps a logical index to a physical index in `storage`, skipping over the gap.  Does not
+    /// check bounds.
+    fn storage_idx(&self, i: usize) -> usize {
+        if i < self.gap.start {
+            i
+        } else {
+            i + (self.gap.end - self.gap.start)
         }
     }
 
     ///Get a reference to the element at the index.
     pub fn get(&self, i: usize) -> Option<&T> {
-        let i = self.get_idx(i);
-        self.buf.get(i)
+        if i >= self.len() {
+            return None;
+        }
+        let idx = self.storage_idx(i);
+        unsafe { Some(&*self.space(idx)) }
     }
 
     ///Get a mutable reference to the element at the index.
     pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
-        let i = self.get_idx(i);
-        self.buf.get_mut(i)
+        if i >= self.len() {
+            return None;
+        }
+        let idx = self.storage_idx(i);
+        unsafe { Some(&mut *self.space_mut(idx)) }
     }
 
     /// Swap the elements at the index.
@@ -125,21 +133,156 @@ impl<T> GapBuffer<T> {
     ///
     /// Panics if there is no element with either index.
     pub fn swap(&mut self, i: usize, j: usize) {
-        let i = self.get_idx(i);
-        let j = self.get_idx(j);
-        self.buf.swap(i, j);
+        assert!(i < self.len() && j < self.len(), "index out of bounds");
+        let si = self.storage_idx(i);
+        let sj = self.storage_idx(j);
+        unsafe {
+            ptr::swap(self.storage.as_mut_ptr().offset(si as isize),
+                      self.storage.as_mut_ptr().offset(sj as isize));
+        }
+    }
+
+    /// Returns the two contiguous physical runs that make up the GapBuffer: the elements before
+    /// the gap, then the elements after it.  Either may be empty.  Unlike a ring-buffer-backed
+    /// split, this is always in logical order -- there is no wrap-around case for callers to
+    /// guard against.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        unsafe {
+            let prefix = slice::from_raw_parts(self.storage.as_ptr(), self.gap.start);
+            let suffix = slice::from_raw_parts(
+                self.storage.as_ptr().offset(self.gap.end as isize),
+                self.storage.capacity() - self.gap.end);
+            (prefix, suffix)
+        }
+    }
+
+    /// Mutable counterpart to `as_slices`.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let gap = self.gap.clone();
+        let cap = self.storage.capacity();
+        unsafe {
+            let prefix = slice::from_raw_parts_mut(self.storage.as_mut_ptr(), gap.start);
+            let suffix = slice::from_raw_parts_mut(
+                self.storage.as_mut_ptr().offset(gap.end as isize),
+                cap - gap.end);
+            (prefix, suffix)
+        }
     }
 
     ///Get the capacity of the GapBuffer without expanding.
     #[inline]
-    pub fn capacity(&self) -> usize { self.buf.capacity() }
+    pub fn capacity(&self) -> usize { self.storage.capacity() }
 
     /// Reserve at least this much additional space for the GapBuffer.
     /// The collection may reserve more space to avoid frequent reallocations.
     ///
     /// Panics if the new capacity overflows uint.
     pub fn reserve(&mut self, additional: usize) {
-        self.buf.reserve(additional)
+        let gap_len = self.gap.end - self.gap.start;
+        if gap_len < additional {
+            self.grow(additional - gap_len);
+        }
+    }
+
+    /// Reallocates into a larger backing allocation, widening the gap by at least
+    /// `min_additional`.  The prefix is copied to the front of the new storage and the suffix to
+    /// the back, with the (larger) gap left in the middle.
+    fn grow(&mut self, min_additional: usize) {
+        let old_cap = self.storage.capacity();
+        let wanted_cap = cmp::max(cmp::max(old_cap * 2, old_cap + min_additional), 4);
+        let prefix_len = self.gap.start;
+        let suffix_len = old_cap - self.gap.end;
+        let mut new_storage: Vec<T> = Vec::with_capacity(wanted_cap);
+        let new_cap = new_storage.capacity();
+        unsafe {
+            ptr::copy_nonoverlapping(self.storage.as_ptr(), new_storage.as_mut_ptr(), prefix_len);
+            ptr::copy_nonoverlapping(
+                self.storage.as_ptr().offset(self.gap.end as isize),
+                new_storage.as_mut_ptr().offset((new_cap - suffix_len) as isize),
+                suffix_len);
+        }
+        self.storage = new_storage;
+        self.gap = prefix_len..(new_cap - suffix_len);
+    }
+
+    /// Returns the current position of the gap (the cursor).  Insertions and removals performed
+    /// at this position via `insert_at_cursor`/`remove_at_cursor` do not need to move the gap.
+    #[inline]
+    pub fn position(&self) -> usize { self.gap.start }
+
+    /// Moves the gap (the cursor) to index `i`, copying elements across the gap as necessary.
+    ///
+    /// Panics if i is greater than the length of the GapBuffer.
+    pub fn set_position(&mut self, i: usize) {
+        assert!(i <= self.len(), "index out of bounds");
+        let gap_len = self.gap.end - self.gap.start;
+        match i.cmp(&self.gap.start) {
+            // Already at the correct position, don't do anything.
+            Ordering::Equal => return,
+            // Move the elements just left of the gap into the space it's vacating on the right.
+            Ordering::Less => {
+                let count = self.gap.start - i;
+                unsafe {
+                    let src = self.storage.as_ptr().offset(i as isize);
+                    let dst = self.storage.as_mut_ptr().offset((i + gap_len) as isize);
+                    ptr::copy(src, dst, count);
+                }
+            }
+            // Move the elements just right of the gap into the space it's vacating on the left.
+            Ordering::Greater => {
+                let count = i - self.gap.start;
+                unsafe {
+                    let src = self.storage.as_ptr().offset(self.gap.end as isize);
+                    let dst = self.storage.as_mut_ptr().offset(self.gap.start as isize);
+                    ptr::copy(src, dst, count);
+                }
+            }
+        }
+        self.gap = i..(i + gap_len);
+    }
+
+    /// Inserts `t` at the current cursor position without moving the gap first.  This is the
+    /// operation to use when performing a run of edits in the same place, since each call costs
+    /// O(1) rather than re-paying the shift.
+    pub fn insert_at_cursor(&mut self, t: T) {
+        if self.gap.start == self.gap.end {
+            self.reserve(1);
+        }
+        unsafe { ptr::write(self.space_mut(self.gap.start), t); }
+        self.gap.start += 1;
+    }
+
+    /// Removes and returns the element just after the current cursor position, without moving
+    /// the gap first.  Returns None if the cursor is at the end of the GapBuffer.
+    pub fn remove_at_cursor(&mut self) -> Option<T> {
+        if self.gap.end >= self.storage.capacity() {
+            return None;
+        }
+        let t = unsafe { ptr::read(self.space(self.gap.end)) };
+        self.gap.end += 1;
+        Some(t)
+    }
+
+    /// Removes the elements in `range` from the GapBuffer, returning an iterator over the
+    /// removed elements.  The gap is moved to `range`'s start once, up front, so removal costs
+    /// a single move plus the length of the range rather than one shift per element.
+    ///
+    /// If the returned iterator is dropped before being fully consumed, the rest of the range is
+    /// removed anyway, leaving the GapBuffer consistent.
+    ///
+    /// Panics if the range is out of bounds, or if the start of the range is greater than its
+    /// end.
+    pub fn drain<R: RangeArgument>(&mut self, range: R) -> Drain<T> {
+        let len = self.len();
+        let start = range.start().unwrap_or(0);
+        let end = range.end().unwrap_or(len);
+        assert!(start <= end, "drain: start is greater than end");
+        assert!(end <= len, "drain: end is out of bounds");
+        self.set_position(start);
+        Drain {
+            buff: self,
+            remaining: end - start,
+        }
     }
 
     ///Get an iterator of this GapBuffer.
@@ -147,44 +290,134 @@ impl<T> GapBuffer<T> {
         Items {
             buff: self,
             idx: 0,
+            end: self.len(),
+        }
+    }
+
+    ///Get a mutable iterator of this GapBuffer.
+    pub fn iter_mut(&mut self) -> IterMut<T> {
+        let end = self.len();
+        IterMut {
+            buff: self,
+            idx: 0,
+            end: end,
         }
     }
 
     ///Get the length of the GapBuffer.
-    pub fn len(&self) -> usize { self.buf.len() }
+    pub fn len(&self) -> usize { self.storage.capacity() - (self.gap.end - self.gap.start) }
 
     ///Is the GapBuffer empty?
     pub fn is_empty(&self) -> bool { self.len() == 0 }
 
     ///Clears the buffer, removing all values.
     pub fn clear(&mut self) {
-        self.offset = 0;
-        self.buf.clear();
+        unsafe {
+            for i in 0..self.gap.start {
+                ptr::read(self.space(i));
+            }
+            for i in self.gap.end..self.storage.capacity() {
+                ptr::read(self.space(i));
+            }
+        }
+        let cap = self.storage.capacity();
+        self.gap = 0..cap;
     }
 
-    /// Insert a new T at a given index (the gap will be shifted to that index).
+    /// Insert a new T at a given index (the gap will be moved to that index).
     ///
-    /// Panics if i is greater than RingBuf's length.
+    /// Panics if i is greater than the GapBuffer's length.
     pub fn insert(&mut self, i: usize, t: T) {
         // Valid indices: [0, len]
         assert!(i <= self.len(), "index out of bounds");
-        // Gap is just before index i
-        self.shift(i);
-        // push_back inserts into the leftmost empty slot in the gap.
-        self.offset += 1;
-        self.buf.push_back(t);
+        self.set_position(i);
+        self.insert_at_cursor(t);
     }
 
-    /// Removes and returns the element at position i from the gap buffer.  The gap will be shifted
+    /// Removes and returns the element at position i from the gap buffer.  The gap will be moved
     /// to just before the index.  Returns None if i is out of bounds.
     pub fn remove(&mut self, i: usize) -> Option<T> {
         // Valid indices: [0, len)
         if self.len() <= i {
             return None;
         }
-        self.shift(i); // Gap is just before index i
-        // pop_front removes from the rightmost empty slot after the gap.
-        self.buf.pop_front()
+        self.set_position(i);
+        self.remove_at_cursor()
+    }
+
+    /// Translates a logical `start..end` range into a physical range in `storage`.
+    ///
+    /// Panics if the range is out of bounds, inverted, or straddles the gap -- move the gap out
+    /// of the way with `set_position` first if that happens.
+    fn slice_range(&self, start: usize, end: usize) -> Range<usize> {
+        assert!(start <= end, "slice index starts at {} but ends at {}", start, end);
+        assert!(end <= self.len(), "range end index {} out of range for GapBuffer of length {}",
+                end, self.len());
+        let gap_len = self.gap.end - self.gap.start;
+        if end <= self.gap.start {
+            start..end
+        } else if start >= self.gap.start {
+            (start + gap_len)..(end + gap_len)
+        } else {
+            panic!("cannot take a contiguous slice from {} to {} across the gap; call \
+                     set_position to move the gap out of the way first", start, end);
+        }
+    }
+}
+
+#[unsafe_destructor]
+impl<T> Drop for GapBuffer<T> {
+    fn drop(&mut self) {
+        // storage's own length is always 0, so its Drop won't touch the elements; drop the
+        // initialized prefix and suffix runs by hand.
+        unsafe {
+            for i in 0..self.gap.start {
+                ptr::read(self.space(i));
+            }
+            for i in self.gap.end..self.storage.capacity() {
+                ptr::read(self.space(i));
+            }
+        }
+    }
+}
+
+impl<T: Clone> Clone for GapBuffer<T> {
+    fn clone(&self) -> GapBuffer<T> {
+        let old_cap = self.storage.capacity();
+        let suffix_len = old_cap - self.gap.end;
+        let mut new_storage: Vec<T> = Vec::with_capacity(old_cap);
+        let new_cap = new_storage.capacity();
+        unsafe {
+            for i in 0..self.gap.start {
+                ptr::write(new_storage.as_mut_ptr().offset(i as isize), (*self.space(i)).clone());
+            }
+            for (j, i) in (self.gap.end..old_cap).enumerate() {
+                ptr::write(new_storage.as_mut_ptr().offset((new_cap - suffix_len + j) as isize),
+                           (*self.space(i)).clone());
+            }
+        }
+        GapBuffer {
+            storage: new_storage,
+            gap: self.gap.start..(new_cap - suffix_len),
+        }
+    }
+}
+
+impl<T> Default for GapBuffer<T> {
+    fn default() -> GapBuffer<T> { GapBuffer::new() }
+}
+
+/// Compares two (prefix, suffix) slice pairs that represent the same logical sequence but may be
+/// split at different points, without flattening either side into a single buffer first.
+fn slices_eq<A, B>(sa: &[A], sb: &[A], oa: &[B], ob: &[B]) -> bool where A: PartialEq<B> {
+    if sa.len() == oa.len() {
+        sa == oa && sb == ob
+    } else if sa.len() < oa.len() {
+        let (oa1, oa2) = oa.split_at(sa.len());
+        sa == oa1 && &sb[..oa2.len()] == oa2 && &sb[oa2.len()..] == ob
+    } else {
+        let (sa1, sa2) = sa.split_at(oa.len());
+        sa1 == oa && sa2 == &ob[..sa2.len()] && sb == &ob[sa2.len()..]
     }
 }
 
@@ -193,8 +426,9 @@ impl <A, B> PartialEq<GapBuffer<B>> for GapBuffer<A> where A: PartialEq<B> {
     #[inline]
     fn eq(&self, other: &GapBuffer<B>) -> bool {
         if self.len() != other.len() { return false }
-        // This isn't as efficient as it could be...
-        self.iter().zip(other.iter()).all( |(x, y)| x == y )
+        let (sa, sb) = self.as_slices();
+        let (oa, ob) = other.as_slices();
+        slices_eq(sa, sb, oa, ob)
     }
 }
 
@@ -240,11 +474,9 @@ impl<A> Ord for GapBuffer<A> where A: Ord {
 //FromIterator
 impl<A> FromIterator<A> for GapBuffer<A> {
     fn from_iter<I: Iterator<Item=A>>(iterator: I) -> GapBuffer<A> {
-        let buf = iterator.collect();
-        GapBuffer {
-            buf: buf,
-            offset: 0,
-        }
+        let mut buf = GapBuffer::new();
+        buf.extend(iterator);
+        buf
     }
 }
 
@@ -252,12 +484,12 @@ impl<A> FromIterator<A> for GapBuffer<A> {
 impl<A> Extend<A> for GapBuffer<A> {
     fn extend<T: Iterator<Item=A>>(&mut self, iterator: T) {
         let len = self.len();
-        // push_back inserts into the leftmost empty slot in the gap.
-        self.shift(len);
-        // So, extending the ring buffer directly at this point will have the same effect as
-        // repeated right insertions.  We don't need to modify the offset because the cursor stays
-        // in place.
-        self.buf.extend(iterator);
+        // Move the gap to the end, then insert at the cursor: the cursor stays put as we go, so
+        // this costs one shift total rather than one per element.
+        self.set_position(len);
+        for t in iterator {
+            self.insert_at_cursor(t);
+        }
     }
 }
 
@@ -281,8 +513,7 @@ impl<T> Index<usize> for GapBuffer<T> {
 
     #[inline]
     fn index<'a>(&'a self, index: &usize) -> &'a T {
-        let index = self.get_idx(*index);
-        &self.buf[index]
+        self.get(*index).expect("index out of bounds")
     }
 }
 
@@ -291,8 +522,93 @@ impl<T> IndexMut<usize> for GapBuffer<T> {
 
     #[inline]
     fn index_mut<'a>(&'a mut self, index: &usize) -> &'a mut T {
-        let index = self.get_idx(*index);
-        &mut self.buf[index]
+        let len = self.len();
+        assert!(*index < len, "index out of bounds");
+        self.get_mut(*index).unwrap()
+    }
+}
+
+impl<T> Index<Range<usize>> for GapBuffer<T> {
+    type Output = [T];
+
+    #[inline]
+    fn index<'a>(&'a self, index: &Range<usize>) -> &'a [T] {
+        let r = self.slice_range(index.start, index.end);
+        unsafe { slice::from_raw_parts(self.space(r.start), r.end - r.start) }
+    }
+}
+
+impl<T> Index<RangeFrom<usize>> for GapBuffer<T> {
+    type Output = [T];
+
+    #[inline]
+    fn index<'a>(&'a self, index: &RangeFrom<usize>) -> &'a [T] {
+        let len = self.len();
+        let r = self.slice_range(index.start, len);
+        unsafe { slice::from_raw_parts(self.space(r.start), r.end - r.start) }
+    }
+}
+
+impl<T> Index<RangeTo<usize>> for GapBuffer<T> {
+    type Output = [T];
+
+    #[inline]
+    fn index<'a>(&'a self, index: &RangeTo<usize>) -> &'a [T] {
+        let r = self.slice_range(0, index.end);
+        unsafe { slice::from_raw_parts(self.space(r.start), r.end - r.start) }
+    }
+}
+
+impl<T> Index<RangeFull> for GapBuffer<T> {
+    type Output = [T];
+
+    #[inline]
+    fn index<'a>(&'a self, _index: &RangeFull) -> &'a [T] {
+        let len = self.len();
+        let r = self.slice_range(0, len);
+        unsafe { slice::from_raw_parts(self.space(r.start), r.end - r.start) }
+    }
+}
+
+impl<T> IndexMut<Range<usize>> for GapBuffer<T> {
+    type Output = [T];
+
+    #[inline]
+    fn index_mut<'a>(&'a mut self, index: &Range<usize>) -> &'a mut [T] {
+        let r = self.slice_range(index.start, index.end);
+        unsafe { slice::from_raw_parts_mut(self.space_mut(r.start), r.end - r.start) }
+    }
+}
+
+impl<T> IndexMut<RangeFrom<usize>> for GapBuffer<T> {
+    type Output = [T];
+
+    #[inline]
+    fn index_mut<'a>(&'a mut self, index: &RangeFrom<usize>) -> &'a mut [T] {
+        let len = self.len();
+        let r = self.slice_range(index.start, len);
+        unsafe { slice::from_raw_parts_mut(self.space_mut(r.start), r.end - r.start) }
+    }
+}
+
+impl<T> IndexMut<RangeTo<usize>> for GapBuffer<T> {
+    type Output = [T];
+
+    #[inline]
+    fn index_mut<'a>(&'a mut self, index: &RangeTo<usize>) -> &'a mut [T] {
+        let r = self.slice_range(0, index.end);
+        unsafe { slice::from_raw_parts_mut(self.space_mut(r.start), r.end - r.start) }
+    }
+}
+
+impl<T> IndexMut<RangeFull> for GapBuffer<T> {
+    type Output = [T];
+
+    #[inline]
+    fn index_mut<'a>(&'a mut self, _index: &RangeFull) -> &'a mut [T] {
+        let len = self.len();
+        let r = self.slice_range(0, len);
+        unsafe { slice::from_raw_parts_mut(self.space_mut(r.start), r.end - r.start) }
     }
 }
 
@@ -302,6 +618,7 @@ impl<T> IndexMut<usize> for GapBuffer<T> {
 pub struct Items<'a, T: 'a> {
     buff: &'a GapBuffer<T>,
     idx: usize,
+    end: usize,
 }
 
 impl<'a, T> Iterator for Items<'a, T> {
@@ -309,6 +626,9 @@ impl<'a, T> Iterator for Items<'a, T> {
 
     #[inline]
     fn next(&mut self) -> Option<&'a T> {
+        if self.idx >= self.end {
+            return None;
+        }
         let next = self.buff.get(self.idx);
         if next.is_some() {
             self.idx += 1;
@@ -318,11 +638,98 @@ impl<'a, T> Iterator for Items<'a, T> {
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = self.buff.len();
+        let len = self.end - self.idx;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Items<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.idx >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        self.buff.get(self.end)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Items<'a, T> {}
+
+/// A mutable iterator over a GapBuffer.  Because the logical sequence maps to two contiguous
+/// physical runs on either side of the gap, this walks the run before the gap followed by the run
+/// after it, yielding mutable references into each in turn.
+pub struct IterMut<'a, T: 'a> {
+    buff: &'a mut GapBuffer<T>,
+    idx: usize,
+    end: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.idx >= self.end {
+            return None;
+        }
+        let idx = self.idx;
+        self.idx += 1;
+        // Each index is yielded at most once, so the references handed out never alias.
+        unsafe { self.buff.get_mut(idx).map(|t| &mut *(t as *mut T)) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.idx;
         (len, Some(len))
     }
 }
 
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.idx >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        let idx = self.end;
+        unsafe { self.buff.get_mut(idx).map(|t| &mut *(t as *mut T)) }
+    }
+}
+
+/// A draining iterator over a range of a GapBuffer, created by `GapBuffer::drain`.
+pub struct Drain<'a, T: 'a> {
+    buff: &'a mut GapBuffer<T>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.buff.remove_at_cursor()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+#[unsafe_destructor]
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        // Remove any elements the caller didn't iterate over, leaving the buffer consistent.
+        for _ in self.by_ref() {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -412,4 +819,100 @@ mod tests {
         }
 
     }
+
+    #[test]
+    fn test_remove_at_cursor_at_end() {
+    //Test that remove_at_cursor obeys its contract at the end of a non-empty buffer.
+        let mut test: GapBuffer<usize> = GapBuffer::new();
+
+        for x in range(0, 5) {
+            test.insert(x, x);
+        }
+
+        test.set_position(test.len());
+        assert!(test.remove_at_cursor().is_none(),
+                "remove_at_cursor at the end of the buffer returned Some");
+        assert!(test.len() == 5, "remove_at_cursor at the end changed the length to {}", test.len());
+
+        for x in range(0, 5) {
+            assert!(test[x] == x, "Index {} corrupted after remove_at_cursor at the end", x);
+        }
+    }
+
+    #[test]
+    fn test_as_slices_round_trip() {
+    //Test that as_slices/as_mut_slices stay in logical order across the gap.
+        let mut test: GapBuffer<usize> = GapBuffer::new();
+
+        for x in range(0, 10) {
+            test.insert(x, x);
+        }
+        test.set_position(4);
+
+        {
+            let (a, b) = test.as_slices();
+            let joined: Vec<usize> = a.iter().chain(b.iter()).map(|&x| x).collect();
+            assert!(joined == vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9], "as_slices out of logical order");
+        }
+
+        {
+            let (a, b) = test.as_mut_slices();
+            for x in a.iter_mut() { *x += 100; }
+            for x in b.iter_mut() { *x += 100; }
+        }
+
+        for x in range(0, 10) {
+            assert!(test[x] == x + 100, "Index {} is {} after as_mut_slices round trip", x, test[x]);
+        }
+    }
+
+    #[test]
+    fn test_grow() {
+    //Test that values and length survive reallocation past the initial capacity.
+        let mut test: GapBuffer<usize> = GapBuffer::with_capacity(4);
+        let initial_cap = test.capacity();
+
+        for x in range(0, 50) {
+            test.insert(x, x);
+        }
+        assert!(test.capacity() > initial_cap, "buffer never grew past its initial capacity");
+        assert!(test.len() == 50, "after growing, buffer length is {}", test.len());
+
+        for x in range(0, 50) {
+            assert!(test[x] == x, "Index {} is {} after growing", x, test[x]);
+        }
+    }
+
+    #[test]
+    fn test_drain_across_gap() {
+    //Test draining a range that spans both runs on either side of the gap.
+        let mut test: GapBuffer<usize> = GapBuffer::new();
+
+        for x in range(0, 10) {
+            test.insert(x, x);
+        }
+        test.set_position(7);
+
+        let drained: Vec<usize> = test.drain(2..8).collect();
+        assert!(drained == vec![2, 3, 4, 5, 6, 7], "drained {:?}", drained);
+        assert!(test.len() == 4, "after draining across the gap, buffer length is {}", test.len());
+
+        let remaining: Vec<usize> = test.iter().map(|&x| x).collect();
+        assert!(remaining == vec![0, 1, 8, 9], "remaining elements were {:?}", remaining);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_slice_across_gap_panics() {
+    //Document that slicing across the gap panics rather than silently handing out a
+    //non-contiguous range; callers must set_position out of the way first.
+        let mut test: GapBuffer<usize> = GapBuffer::new();
+
+        for x in range(0, 10) {
+            test.insert(x, x);
+        }
+        test.set_position(5);
+
+        let _ = &test[2..8];
+    }
 }
@@ -0,0 +1,229 @@
+//  Copyright 2014 David Lee Aronson.
+//
+//  This program is free software: you can redistribute it and/or modify it under the terms of the
+//  GNU Lesser General Public License as published by the Free Software Foundation, either version 3
+//  of the License, or (at your option) any later version.
+//
+//  This program is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+//  without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+//  the GNU Lesser General Public License for more details.
+//
+//  You should have received a copy of the GNU Lesser General Public License along with this
+//  program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A text buffer built on top of `GapBuffer<char>` whose cursor motion and deletion snap to
+//! grapheme-cluster boundaries instead of raw scalar values, so that multi-scalar emoji and
+//! combining marks move and delete as a single unit.
+//!
+//! The break rules implemented here are a small, practical subset of UAX #29 (no break within
+//! CR x LF, no break before an Extend/ZWJ scalar, no break after a ZWJ either, no break between
+//! two Regional Indicators) -- enough to handle combining diacritics, ZWJ sequences and flag
+//! emoji, but not a complete implementation of the annex.  Regional Indicators are not split
+//! into pairs: a run of them stays one cluster, so adjacent flags move and delete together.
+
+use super::GapBuffer;
+use std::cmp::Ordering;
+
+/// A coarse grapheme-cluster-break category for a single scalar value.
+#[derive(Clone, Copy, PartialEq, Show)]
+pub enum GraphemeCat {
+    /// Carriage return (`\r`).
+    CR,
+    /// Line feed (`\n`).
+    LF,
+    /// A combining mark or other scalar that always attaches to the previous one (UAX #29
+    /// Extend).
+    Extend,
+    /// Zero-width joiner; like `Extend`, it never starts a new cluster.
+    ZWJ,
+    /// Regional indicator symbols, which pair up to form flag emoji.
+    RegionalIndicator,
+    /// Everything else.
+    Any,
+}
+
+/// Sorted, non-overlapping `(lo, hi, category)` ranges, binary-searched by `grapheme_category`.
+static GRAPHEME_TABLE: &'static [(char, char, GraphemeCat)] = &[
+    ('\n', '\n', GraphemeCat::LF),
+    ('\r', '\r', GraphemeCat::CR),
+    ('\u{0300}', '\u{036f}', GraphemeCat::Extend),
+    ('\u{200d}', '\u{200d}', GraphemeCat::ZWJ),
+    ('\u{1f1e6}', '\u{1f1ff}', GraphemeCat::RegionalIndicator),
+];
+
+/// Looks up the grapheme-cluster-break category of a scalar value.
+pub fn grapheme_category(c: char) -> GraphemeCat {
+    let idx = GRAPHEME_TABLE.binary_search_by(|&(lo, hi, _)| {
+        if c < lo { Ordering::Greater }
+        else if c > hi { Ordering::Less }
+        else { Ordering::Equal }
+    });
+    match idx {
+        Ok(i) => GRAPHEME_TABLE[i].2,
+        Err(_) => GraphemeCat::Any,
+    }
+}
+
+/// Returns true if there is a grapheme-cluster break between two adjacent scalars with the given
+/// categories (i.e. `before` ends a cluster and `after` starts a new one).
+fn is_boundary(before: GraphemeCat, after: GraphemeCat) -> bool {
+    match (before, after) {
+        (GraphemeCat::CR, GraphemeCat::LF) => false,
+        (_, GraphemeCat::Extend) => false,
+        (_, GraphemeCat::ZWJ) => false,
+        (GraphemeCat::ZWJ, _) => false,
+        (GraphemeCat::RegionalIndicator, GraphemeCat::RegionalIndicator) => false,
+        _ => true,
+    }
+}
+
+/// A `GapBuffer<char>` whose cursor motion and deletion operate on whole grapheme clusters.
+pub struct TextGapBuffer {
+    buf: GapBuffer<char>,
+}
+
+impl TextGapBuffer {
+    ///Constructs an empty TextGapBuffer.
+    pub fn new() -> TextGapBuffer {
+        TextGapBuffer { buf: GapBuffer::new() }
+    }
+
+    ///Constructs a TextGapBuffer containing the scalars of `s`.
+    pub fn from_str(s: &str) -> TextGapBuffer {
+        let mut buf = GapBuffer::new();
+        buf.extend(s.chars());
+        // extend() leaves the cursor at the end (where it appended); a freshly loaded buffer
+        // should start at the front.
+        buf.set_position(0);
+        TextGapBuffer { buf: buf }
+    }
+
+    ///The underlying scalar-indexed GapBuffer.
+    pub fn buffer(&self) -> &GapBuffer<char> { &self.buf }
+
+    ///The number of scalar values (not grapheme clusters) in the buffer.
+    pub fn len(&self) -> usize { self.buf.len() }
+
+    ///Is the TextGapBuffer empty?
+    pub fn is_empty(&self) -> bool { self.buf.is_empty() }
+
+    ///The cursor's current scalar index.
+    pub fn position(&self) -> usize { self.buf.position() }
+
+    /// Moves the cursor left, over one whole grapheme cluster.  Does nothing if the cursor is
+    /// already at the start of the buffer.
+    pub fn move_left_grapheme(&mut self) {
+        let mut pos = self.buf.position();
+        if pos == 0 {
+            return;
+        }
+        pos -= 1;
+        while pos > 0 {
+            let before = grapheme_category(*self.buf.get(pos - 1).unwrap());
+            let after = grapheme_category(*self.buf.get(pos).unwrap());
+            if is_boundary(before, after) {
+                break;
+            }
+            pos -= 1;
+        }
+        self.buf.set_position(pos);
+    }
+
+    /// Moves the cursor right, over one whole grapheme cluster.  Does nothing if the cursor is
+    /// already at the end of the buffer.
+    pub fn move_right_grapheme(&mut self) {
+        let len = self.buf.len();
+        let mut pos = self.buf.position();
+        if pos >= len {
+            return;
+        }
+        pos += 1;
+        while pos < len {
+            let before = grapheme_category(*self.buf.get(pos - 1).unwrap());
+            let after = grapheme_category(*self.buf.get(pos).unwrap());
+            if is_boundary(before, after) {
+                break;
+            }
+            pos += 1;
+        }
+        self.buf.set_position(pos);
+    }
+
+    /// Removes the whole grapheme cluster just before the cursor.  Does nothing if the cursor is
+    /// at the start of the buffer.
+    pub fn delete_grapheme_back(&mut self) {
+        let end = self.buf.position();
+        if end == 0 {
+            return;
+        }
+        let mut start = end - 1;
+        while start > 0 {
+            let before = grapheme_category(*self.buf.get(start - 1).unwrap());
+            let after = grapheme_category(*self.buf.get(start).unwrap());
+            if is_boundary(before, after) {
+                break;
+            }
+            start -= 1;
+        }
+        for _ in self.buf.drain(start..end) {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::TextGapBuffer;
+
+    #[test]
+    fn test_combining_mark() {
+    //Test that a base scalar plus a combining mark move and delete as one cluster.
+        let mut test = TextGapBuffer::from_str("a\u{0301}b");
+        assert!(test.len() == 3, "buffer length is {}", test.len());
+
+        test.move_right_grapheme();
+        assert!(test.position() == 2, "cursor stopped at {} instead of skipping the combining \
+                 mark cluster", test.position());
+
+        test.delete_grapheme_back();
+        assert!(test.len() == 1, "buffer length is {} after deleting the base+mark cluster",
+                test.len());
+        assert!(test.position() == 0, "cursor stopped at {} after deleting the cluster",
+                test.position());
+
+        test.move_right_grapheme();
+        assert!(test.position() == 1, "cursor stopped at {} moving over the remaining scalar",
+                test.position());
+    }
+
+    #[test]
+    fn test_zwj_sequence() {
+    //Test that scalars joined by a ZWJ move as a single cluster.
+        let mut test = TextGapBuffer::from_str("a\u{200d}b");
+        assert!(test.len() == 3, "buffer length is {}", test.len());
+
+        test.move_right_grapheme();
+        assert!(test.position() == 3, "cursor stopped at {} instead of clearing the ZWJ run",
+                test.position());
+
+        test.move_left_grapheme();
+        assert!(test.position() == 0, "cursor stopped at {} instead of returning to the start",
+                test.position());
+    }
+
+    #[test]
+    fn test_cr_lf() {
+    //Test that a CR immediately followed by an LF is never split.
+        let mut test = TextGapBuffer::from_str("a\r\nb");
+        assert!(test.len() == 4, "buffer length is {}", test.len());
+
+        test.move_right_grapheme();
+        assert!(test.position() == 1, "cursor stopped at {} after first move", test.position());
+
+        test.move_right_grapheme();
+        assert!(test.position() == 3, "cursor stopped at {} instead of clearing the CRLF pair",
+                test.position());
+
+        test.delete_grapheme_back();
+        assert!(test.len() == 2, "buffer length is {} after deleting the CRLF cluster", test.len());
+    }
+}